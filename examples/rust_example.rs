@@ -6,76 +6,29 @@
  * 
  * Add to Cargo.toml:
  * [dependencies]
- * rusqlite = { version = "0.31", features = ["loadable_extension"] }
+ * rusqlite = { version = "0.40", features = ["bundled"] }
+ * sqlite-icu-tokenizer = "0.1"
  */
 
 use rusqlite::{Connection, Result, params};
-use std::env;
+use sqlite_icu_tokenizer::register_icu_tokenizer;
 
-/// Get the appropriate binary filename for the current platform
-fn get_platform_binary() -> Result<String, String> {
-    let os = env::consts::OS;
-    let arch = env::consts::ARCH;
-    
-    let binary = match (os, arch) {
-        ("linux", "x86_64") => "fts5icu-linux-x86_64.so",
-        ("macos", "x86_64") => "fts5icu-darwin-x86_64.dylib",
-        ("macos", "aarch64") => "fts5icu-darwin-arm64.dylib",
-        ("windows", "x86_64") => "fts5icu-win32-x86_64.dll",
-        _ => return Err(format!("No pre-built binary available for {}-{}", os, arch)),
-    };
-    
-    Ok(binary.to_string())
-}
-
-/// Load the ICU tokenizer extension into SQLite connection
+/// Register the ICU tokenizer directly on the connection.
+///
+/// No platform-specific binary is loaded: the tokenizer is compiled into the
+/// application, so `tokenize='icu'` works anywhere the crate builds.
 fn setup_icu_extension(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
-    // Determine platform-specific binary
-    let binary_name = get_platform_binary()
-        .map_err(|e| format!("Platform detection failed: {}", e))?;
-    
-    // Check if binary exists
-    if !std::path::Path::new(&binary_name).exists() {
-        eprintln!("❌ Binary not found: {}", binary_name);
-        eprintln!("Please download from: https://github.com/tkys/sqlite-icu-tokenizer/releases/latest");
-        return Err("Binary not found".into());
-    }
-    
-    // Load the extension
-    unsafe {
-        conn.load_extension_enable();
-        match conn.load_extension(&binary_name, None) {
-            Ok(_) => {
-                println!("✅ ICU extension loaded successfully: {}", binary_name);
-                conn.load_extension_disable();
-                Ok(())
-            },
-            Err(e) => {
-                conn.load_extension_disable();
-                eprintln!("❌ Failed to load ICU extension: {}", e);
-                eprintln!("Make sure:");
-                eprintln!("1. ICU libraries are installed (libicu-dev/libicu)");
-                eprintln!("2. SQLite was compiled with extension support");
-                eprintln!("3. The binary file has correct permissions");
-                Err(e.into())
-            }
-        }
-    }
-}
-
-#[derive(Debug)]
-struct Document {
-    id: i32,
-    title: String,
-    content: String,
+    register_icu_tokenizer(conn)?;
+    println!("✅ ICU tokenizer registered");
+    Ok(())
 }
 
 #[derive(Debug)]
-struct SearchResult {
-    id: i32,
-    title: String,
-    snippet: String,
-    relevance: f64,
+pub struct SearchResult {
+    pub id: i32,
+    pub title: String,
+    pub snippet: String,
+    pub relevance: f64,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -215,7 +168,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Demonstrate prepared statement reuse for performance
     println!("\n⚡ Performance Example: Reusable Prepared Statements");
     
-    let performance_search = conn.prepare("
+    let mut performance_search = conn.prepare("
         SELECT COUNT(*) as count
         FROM documents 
         WHERE documents MATCH ?
@@ -303,7 +256,7 @@ impl IcuSearch {
             })
         })?;
         
-        results.collect::<Result<Vec<_>, _>>().map_err(|e| e.into())
+        results.collect::<Result<Vec<_>, _>>()
     }
 }
 
@@ -311,13 +264,6 @@ impl IcuSearch {
 mod tests {
     use super::*;
     
-    #[test]
-    fn test_platform_binary_detection() {
-        let binary = get_platform_binary();
-        assert!(binary.is_ok());
-        assert!(binary.unwrap().contains("fts5icu-"));
-    }
-    
     #[test]
     fn test_icu_search_api() -> Result<(), Box<dyn std::error::Error>> {
         let search = IcuSearch::new(":memory:")?;
@@ -0,0 +1,105 @@
+//! Overlapping character-bigram expansion for CJK runs.
+//!
+//! ICU dictionary segmentation frequently over- or mis-splits CJK compounds
+//! (e.g. `全文検索機能`), hurting recall. The opt-in `bigram` mode supplements
+//! dictionary words with overlapping character bigrams over contiguous runs of
+//! Han/Hiragana/Katakana characters: a run `c0 c1 c2 … cn` yields the tokens
+//! `c0c1`, `c1c2`, `c2c3`, … and a single-character run yields that one
+//! character. Each bigram carries the source byte range spanning its two
+//! characters.
+//!
+//! The expansion is deterministic and text-only, so the tokenizer runs it
+//! identically at `FTS5_TOKENIZE_DOCUMENT` and `FTS5_TOKENIZE_QUERY` time and
+//! phrase matches line up. The [`BigramMode::Colocated`] variant is handled by
+//! the tokenizer glue, which reports the bigrams as colocated synonyms of the
+//! dictionary word so `snippet()`/`highlight()` still cover whole words.
+
+use crate::segmenter::Word;
+
+/// How bigram tokens relate to the dictionary tokens they supplement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BigramMode {
+    /// No bigram expansion (the default).
+    #[default]
+    Off,
+    /// Emit bigrams as independent tokens alongside the dictionary words.
+    Standalone,
+    /// Emit bigrams as colocated synonyms sharing the dictionary word's range.
+    Colocated,
+}
+
+/// Whether `c` participates in CJK bigram runs (Han, Hiragana, Katakana).
+pub fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x309F |   // Hiragana
+        0x30A0..=0x30FF |   // Katakana
+        0x3400..=0x4DBF |   // CJK Unified Ideographs Extension A
+        0x4E00..=0x9FFF |   // CJK Unified Ideographs
+        0xF900..=0xFAFF |   // CJK Compatibility Ideographs
+        0x20000..=0x2A6DF | // CJK Unified Ideographs Extension B
+        0x2F800..=0x2FA1F   // CJK Compatibility Ideographs Supplement
+    )
+}
+
+/// Produce overlapping bigram ranges for every contiguous CJK run in `text`.
+///
+/// Ranges are UTF-8 byte offsets into `text`. A run of a single CJK character
+/// yields that character's range; longer runs yield one bigram per adjacent
+/// pair.
+pub fn bigrams(text: &str) -> Vec<Word> {
+    let mut out = Vec::new();
+    // `(start, end)` byte ranges of the CJK characters in the current run.
+    let mut run: Vec<(usize, usize)> = Vec::new();
+
+    let flush = |run: &mut Vec<(usize, usize)>, out: &mut Vec<Word>| {
+        match run.len() {
+            0 => {}
+            1 => out.push(Word { start: run[0].0, end: run[0].1 }),
+            _ => {
+                for pair in run.windows(2) {
+                    out.push(Word { start: pair[0].0, end: pair[1].1 });
+                }
+            }
+        }
+        run.clear();
+    };
+
+    for (idx, ch) in text.char_indices() {
+        if is_cjk(ch) {
+            run.push((idx, idx + ch.len_utf8()));
+        } else if !run.is_empty() {
+            flush(&mut run, &mut out);
+        }
+    }
+    flush(&mut run, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(text: &str) -> Vec<&str> {
+        bigrams(text).into_iter().map(|w| &text[w.start..w.end]).collect()
+    }
+
+    #[test]
+    fn overlapping_bigrams_for_han_run() {
+        assert_eq!(texts("全文検索"), vec!["全文", "文検", "検索"]);
+    }
+
+    #[test]
+    fn single_character_run_emits_itself() {
+        assert_eq!(texts("猫"), vec!["猫"]);
+    }
+
+    #[test]
+    fn non_cjk_runs_are_ignored() {
+        assert!(bigrams("hello world").is_empty());
+    }
+
+    #[test]
+    fn runs_are_split_on_non_cjk() {
+        assert_eq!(texts("全文 検索"), vec!["全文", "検索"]);
+    }
+}
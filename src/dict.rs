@@ -0,0 +1,197 @@
+//! External stopword and synonym dictionaries.
+//!
+//! Both are loaded once at tokenizer creation (`xCreate`) from paths given in
+//! the tokenizer arguments — `stopwords=/etc/ja.stop synonyms=/etc/syn.tsv` —
+//! and cached on the tokenizer instance so the files are never reparsed per
+//! row. Stopwords are dropped from the token stream; synonyms are emitted as
+//! colocated tokens sharing the original token's source offset so a query for
+//! any surface form matches the indexed document.
+//!
+//! Entries are written in surface form in the files. Because lookups happen
+//! against the *filtered* token, [`StopWords::normalize_keys`] and
+//! [`SynonymMap::normalize_keys`] run every key through the configured filter
+//! chain once at load time, so a `stem=en` table matches the stopword
+//! `running` and a `casefold` table matches the synonym key `Tokyo`.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::filters::FilterPipeline;
+
+/// Run `key` through `pipeline`, falling back to the verbatim key when a filter
+/// drops it so no dictionary entry is silently lost.
+fn normalize(pipeline: &FilterPipeline, key: &str) -> String {
+    pipeline
+        .apply(key)
+        .map(|t| t.into_owned())
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// A set of tokens to drop during tokenization.
+///
+/// The file holds one stopword per line; blank lines and lines beginning with
+/// `#` are ignored.
+pub struct StopWords {
+    words: HashSet<String>,
+}
+
+impl StopWords {
+    /// Load stopwords from `path`.
+    pub fn load(path: &str) -> Result<Self> {
+        let contents = read_file(path)?;
+        let words = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        Ok(StopWords { words })
+    }
+
+    /// Whether `token` should be dropped.
+    pub fn contains(&self, token: &str) -> bool {
+        self.words.contains(token)
+    }
+
+    /// Rewrite every entry through `pipeline` so lookups line up with the
+    /// filtered token form produced during tokenization (e.g. with `stem=en`
+    /// the entry `running` becomes `run`).
+    pub fn normalize_keys(&mut self, pipeline: &FilterPipeline) {
+        self.words = self.words.iter().map(|w| normalize(pipeline, w)).collect();
+    }
+}
+
+/// A map from a surface token to its synonyms.
+///
+/// The file is tab-separated: the first field is the surface form and the
+/// remaining fields are its synonyms (`東京\tトウキョウ\tTokyo`). Blank lines and
+/// `#` comments are ignored.
+pub struct SynonymMap {
+    map: HashMap<String, Vec<String>>,
+}
+
+impl SynonymMap {
+    /// Load a synonym map from `path`.
+    pub fn load(path: &str) -> Result<Self> {
+        let contents = read_file(path)?;
+        let mut map: HashMap<String, Vec<String>> = HashMap::new();
+        for line in contents.lines() {
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split('\t').filter(|f| !f.is_empty());
+            if let Some(surface) = fields.next() {
+                let synonyms: Vec<String> = fields.map(str::to_string).collect();
+                if !synonyms.is_empty() {
+                    map.entry(surface.to_string()).or_default().extend(synonyms);
+                }
+            }
+        }
+        Ok(SynonymMap { map })
+    }
+
+    /// Synonyms for `token`, or an empty slice if it has none.
+    pub fn get(&self, token: &str) -> &[String] {
+        self.map.get(token).map_or(&[], Vec::as_slice)
+    }
+
+    /// Rewrite every surface key through `pipeline` so lookups line up with the
+    /// filtered token form (e.g. with `casefold` the key `Tokyo` becomes
+    /// `tokyo`). The synonym values are left as written; they are filtered again
+    /// when emitted.
+    pub fn normalize_keys(&mut self, pipeline: &FilterPipeline) {
+        let mut merged: HashMap<String, Vec<String>> = HashMap::new();
+        for (key, values) in self.map.drain() {
+            // Distinct surface keys can fold to the same normalized form; merge
+            // their synonym lists rather than letting one clobber the other.
+            merged.entry(normalize(pipeline, &key)).or_default().extend(values);
+        }
+        self.map = merged;
+    }
+}
+
+/// Read a dictionary file, wrapping I/O errors with the offending path.
+fn read_file(path: &str) -> Result<String> {
+    fs::read_to_string(Path::new(path)).map_err(|source| Error::Io {
+        path: path.to_string(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_file(name: &str, contents: &str) -> String {
+        let mut path = std::env::temp_dir();
+        path.push(name);
+        let mut file = fs::File::create(&path).unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn stopwords_skip_comments_and_blanks() {
+        let path = temp_file("icu_stop_test.txt", "# comment\nthe\n\na\n");
+        let stop = StopWords::load(&path).unwrap();
+        assert!(stop.contains("the"));
+        assert!(stop.contains("a"));
+        assert!(!stop.contains("#"));
+    }
+
+    #[test]
+    fn synonyms_parse_tsv() {
+        let path = temp_file("icu_syn_test.tsv", "東京\tトウキョウ\tTokyo\n# c\nempty\n");
+        let syn = SynonymMap::load(&path).unwrap();
+        assert_eq!(syn.get("東京"), &["トウキョウ".to_string(), "Tokyo".to_string()]);
+        assert!(syn.get("empty").is_empty());
+        assert!(syn.get("missing").is_empty());
+    }
+
+    #[test]
+    fn normalize_keys_rewrites_through_pipeline() {
+        use crate::filters::{CaseFold, Stem};
+
+        let mut pipeline = FilterPipeline::new();
+        pipeline.push(Box::new(Stem::new("en").unwrap()));
+        let path = temp_file("icu_stop_norm.txt", "running\n");
+        let mut stop = StopWords::load(&path).unwrap();
+        stop.normalize_keys(&pipeline);
+        // The stemmed token `run` is what tokenization will look up.
+        assert!(stop.contains("run"));
+
+        let mut pipeline = FilterPipeline::new();
+        pipeline.push(Box::new(CaseFold));
+        let path = temp_file("icu_syn_norm.tsv", "Tokyo\t東京\n");
+        let mut syn = SynonymMap::load(&path).unwrap();
+        syn.normalize_keys(&pipeline);
+        assert_eq!(syn.get("tokyo"), &["東京".to_string()]);
+    }
+
+    #[test]
+    fn normalize_keys_merges_colliding_keys() {
+        use crate::filters::CaseFold;
+
+        let mut pipeline = FilterPipeline::new();
+        pipeline.push(Box::new(CaseFold));
+        // `US` and `us` both fold to `us`; their synonym lists must merge.
+        let path = temp_file("icu_syn_merge.tsv", "US\t合衆国\nus\tUSA\n");
+        let mut syn = SynonymMap::load(&path).unwrap();
+        syn.normalize_keys(&pipeline);
+        let mut got = syn.get("us").to_vec();
+        got.sort();
+        assert_eq!(got, vec!["USA".to_string(), "合衆国".to_string()]);
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        assert!(matches!(
+            StopWords::load("/no/such/path.stop"),
+            Err(Error::Io { .. })
+        ));
+    }
+}
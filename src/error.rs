@@ -0,0 +1,70 @@
+//! Error type for ICU tokenizer configuration and setup.
+//!
+//! Configuration errors surface while parsing the `tokenize='icu ...'`
+//! argument list handed to FTS5 at `xCreate` time. They are kept separate
+//! from `rusqlite::Error` so the parser can be unit-tested on its own; the
+//! FTS5 glue converts them into a SQLite error when a table is created with a
+//! bad tokenizer spec.
+
+use std::fmt;
+
+/// Errors raised while parsing `tokenize='icu ...'` arguments or building the
+/// tokenizer pipeline.
+#[derive(Debug)]
+pub enum Error {
+    /// An unrecognized option token appeared in the argument list.
+    UnknownOption(String),
+    /// An option that requires a value was given without one.
+    MissingValue(&'static str),
+    /// An option value could not be parsed (e.g. a non-numeric diacritic level).
+    InvalidValue {
+        /// The option whose value was rejected.
+        option: &'static str,
+        /// The offending value, as written by the user.
+        value: String,
+    },
+    /// A stemmer was requested for a language Snowball does not support.
+    UnknownLanguage(String),
+    /// ICU could not build a break iterator for the requested locale.
+    InvalidLocale(String),
+    /// A stopword or synonym dictionary file could not be read.
+    Io {
+        /// The path that failed to load.
+        path: String,
+        /// The underlying I/O error.
+        source: std::io::Error,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownOption(opt) => write!(f, "unknown icu tokenizer option '{}'", opt),
+            Error::MissingValue(opt) => write!(f, "icu tokenizer option '{}' requires a value", opt),
+            Error::InvalidValue { option, value } => {
+                write!(f, "invalid value '{}' for icu tokenizer option '{}'", value, option)
+            }
+            Error::UnknownLanguage(lang) => {
+                write!(f, "no stemmer available for language '{}'", lang)
+            }
+            Error::InvalidLocale(locale) => {
+                write!(f, "invalid icu locale '{}'", locale)
+            }
+            Error::Io { path, source } => {
+                write!(f, "failed to read dictionary '{}': {}", path, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Convenience alias used throughout the crate's configuration paths.
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,284 @@
+//! Post-segmentation token filters.
+//!
+//! After the ICU word segmenter emits a word, each enabled filter rewrites the
+//! token text in turn. Filters touch the token *text* only: FTS5 needs the
+//! original source byte range to drive `snippet()`/`highlight()`, so a filter
+//! that maps `"café"` to `"cafe"` must still report the bytes of `"café"` in the
+//! document. The pipeline therefore returns only the rewritten text and leaves
+//! the caller holding the original offsets.
+//!
+//! The filters run in the order they were listed in the tokenizer arguments, so
+//! `tokenize='icu nfkc casefold stem=en'` normalizes, then case-folds, then
+//! stems.
+
+use std::borrow::Cow;
+
+use rust_stemmers::{Algorithm, Stemmer};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::error::{Error, Result};
+
+/// A single stage in the token-filter pipeline.
+///
+/// Implementors rewrite the token text and return the result, borrowing the
+/// input unchanged when the filter is a no-op for that token. Returning `None`
+/// drops the token from the stream entirely.
+pub trait TokenFilter: Send + Sync {
+    /// Transform `token`, or return `None` to drop it.
+    fn filter<'a>(&self, token: Cow<'a, str>) -> Option<Cow<'a, str>>;
+}
+
+/// An ordered chain of [`TokenFilter`]s applied to every segmented word.
+#[derive(Default)]
+pub struct FilterPipeline {
+    filters: Vec<Box<dyn TokenFilter>>,
+}
+
+impl FilterPipeline {
+    /// An empty pipeline that passes tokens through unchanged.
+    pub fn new() -> Self {
+        FilterPipeline { filters: Vec::new() }
+    }
+
+    /// Append a filter to the end of the chain.
+    pub fn push(&mut self, filter: Box<dyn TokenFilter>) {
+        self.filters.push(filter);
+    }
+
+    /// `true` when no filters are configured.
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// Run `token` through every filter in order.
+    ///
+    /// Returns the rewritten text, or `None` if a filter dropped the token.
+    pub fn apply<'a>(&self, token: &'a str) -> Option<Cow<'a, str>> {
+        let mut text = Cow::Borrowed(token);
+        for filter in &self.filters {
+            text = filter.filter(text)?;
+        }
+        Some(text)
+    }
+}
+
+/// Unicode NFKC normalization, unifying full/half-width and compatibility forms.
+pub struct Nfkc;
+
+impl TokenFilter for Nfkc {
+    fn filter<'a>(&self, token: Cow<'a, str>) -> Option<Cow<'a, str>> {
+        let normalized: String = token.nfkc().collect();
+        if normalized == token.as_ref() {
+            Some(token)
+        } else {
+            Some(Cow::Owned(normalized))
+        }
+    }
+}
+
+/// Unicode default case folding, for case-insensitive matching beyond ASCII.
+///
+/// Unlike [`str::to_lowercase`], this uses Unicode's full case-folding mapping,
+/// so characters that fold without simply lowercasing are normalized too: `ß`
+/// folds to `ss` (matching `STRASSE`), `ς`/`Σ` both fold to `σ`, and `İ` folds
+/// to `i̇`. Folding is the form meant for caseless comparison, not display.
+pub struct CaseFold;
+
+impl TokenFilter for CaseFold {
+    fn filter<'a>(&self, token: Cow<'a, str>) -> Option<Cow<'a, str>> {
+        let folded = caseless::default_case_fold_str(&token);
+        if folded == token.as_ref() {
+            Some(token)
+        } else {
+            Some(Cow::Owned(folded))
+        }
+    }
+}
+
+/// Transliteration to a searchable ASCII form via [`any_ascii`], collapsing
+/// accented Latin and non-Latin scripts (e.g. `"café"` → `"cafe"`).
+pub struct Transliterate;
+
+impl TokenFilter for Transliterate {
+    fn filter<'a>(&self, token: Cow<'a, str>) -> Option<Cow<'a, str>> {
+        if token.is_ascii() {
+            return Some(token);
+        }
+        Some(Cow::Owned(any_ascii::any_ascii(&token)))
+    }
+}
+
+/// Snowball/Porter stemming keyed by a language code.
+pub struct Stem {
+    stemmer: Stemmer,
+}
+
+impl Stem {
+    /// Build a stemmer for an ISO-639 language code (e.g. `"en"`, `"fr"`).
+    ///
+    /// Returns [`Error::UnknownLanguage`] for codes Snowball does not cover.
+    pub fn new(lang: &str) -> Result<Self> {
+        let algorithm = algorithm_for(lang)
+            .ok_or_else(|| Error::UnknownLanguage(lang.to_string()))?;
+        Ok(Stem { stemmer: Stemmer::create(algorithm) })
+    }
+}
+
+impl TokenFilter for Stem {
+    fn filter<'a>(&self, token: Cow<'a, str>) -> Option<Cow<'a, str>> {
+        match self.stemmer.stem(&token) {
+            Cow::Borrowed(_) => Some(token),
+            Cow::Owned(stemmed) => Some(Cow::Owned(stemmed)),
+        }
+    }
+}
+
+/// Map an ISO-639 language code to a Snowball algorithm.
+fn algorithm_for(lang: &str) -> Option<Algorithm> {
+    let algorithm = match lang.to_ascii_lowercase().as_str() {
+        "ar" => Algorithm::Arabic,
+        "da" => Algorithm::Danish,
+        "nl" => Algorithm::Dutch,
+        "en" => Algorithm::English,
+        "fi" => Algorithm::Finnish,
+        "fr" => Algorithm::French,
+        "de" => Algorithm::German,
+        "el" => Algorithm::Greek,
+        "hu" => Algorithm::Hungarian,
+        "it" => Algorithm::Italian,
+        "no" => Algorithm::Norwegian,
+        "pt" => Algorithm::Portuguese,
+        "ro" => Algorithm::Romanian,
+        "ru" => Algorithm::Russian,
+        "es" => Algorithm::Spanish,
+        "sv" => Algorithm::Swedish,
+        "ta" => Algorithm::Tamil,
+        "tr" => Algorithm::Turkish,
+        _ => return None,
+    };
+    Some(algorithm)
+}
+
+/// Diacritic removal with the same `0|1|2` aggressiveness scale as
+/// `unicode61`'s `remove_diacritics` option.
+///
+/// Level `0` keeps the token untouched. Level `1` strips only the combining
+/// marks already present in the token, leaving precomposed characters (e.g.
+/// `ï`, `ø`) intact — the conservative behavior. Level `2` additionally
+/// decomposes the token (NFD) first, so diacritics on precomposed characters
+/// are removed as well before recomposing. This is the more aggressive mode.
+///
+/// The exact codepoint table differs from SQLite's built-in `unicode61`; only
+/// the level semantics (1 conservative, 2 aggressive) are mirrored.
+pub struct RemoveDiacritics {
+    level: u8,
+}
+
+impl RemoveDiacritics {
+    /// Build the filter for a level in `0..=2`.
+    pub fn new(level: u8) -> Result<Self> {
+        if level > 2 {
+            return Err(Error::InvalidValue {
+                option: "remove_diacritics",
+                value: level.to_string(),
+            });
+        }
+        Ok(RemoveDiacritics { level })
+    }
+}
+
+impl TokenFilter for RemoveDiacritics {
+    fn filter<'a>(&self, token: Cow<'a, str>) -> Option<Cow<'a, str>> {
+        let stripped: String = match self.level {
+            0 => return Some(token),
+            // Conservative: drop combining marks as written, without
+            // decomposing precomposed characters.
+            1 => token.chars().filter(|c| !is_combining_mark(*c)).collect(),
+            // Aggressive: decompose first so precomposed diacritics are removed
+            // too, then recompose the base characters.
+            _ => token.nfd().filter(|c| !is_combining_mark(*c)).nfc().collect(),
+        };
+        if stripped == token.as_ref() {
+            Some(token)
+        } else {
+            Some(Cow::Owned(stripped))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applied(pipeline: &FilterPipeline, token: &str) -> Option<String> {
+        pipeline.apply(token).map(|t| t.into_owned())
+    }
+
+    #[test]
+    fn nfkc_unifies_fullwidth() {
+        let mut pipeline = FilterPipeline::new();
+        pipeline.push(Box::new(Nfkc));
+        assert_eq!(applied(&pipeline, "ＡＢＣ").as_deref(), Some("ABC"));
+    }
+
+    #[test]
+    fn casefold_lowercases_beyond_ascii() {
+        let mut pipeline = FilterPipeline::new();
+        pipeline.push(Box::new(CaseFold));
+        assert_eq!(applied(&pipeline, "Σοφία").as_deref(), Some("σοφία"));
+    }
+
+    #[test]
+    fn casefold_folds_where_lowercase_would_not() {
+        // Full case folding maps ß to "ss", so "STRASSE" and "Straße" collide.
+        let mut pipeline = FilterPipeline::new();
+        pipeline.push(Box::new(CaseFold));
+        assert_eq!(applied(&pipeline, "STRASSE").as_deref(), Some("strasse"));
+        assert_eq!(applied(&pipeline, "Straße").as_deref(), Some("strasse"));
+    }
+
+    #[test]
+    fn transliterate_collapses_accents() {
+        let mut pipeline = FilterPipeline::new();
+        pipeline.push(Box::new(Transliterate));
+        assert_eq!(applied(&pipeline, "café").as_deref(), Some("cafe"));
+    }
+
+    #[test]
+    fn stem_reduces_inflections() {
+        let mut pipeline = FilterPipeline::new();
+        pipeline.push(Box::new(Stem::new("en").unwrap()));
+        assert_eq!(applied(&pipeline, "running").as_deref(), Some("run"));
+    }
+
+    #[test]
+    fn remove_diacritics_level1_strips_combining_marks_only() {
+        let mut pipeline = FilterPipeline::new();
+        pipeline.push(Box::new(RemoveDiacritics::new(1).unwrap()));
+        // Decomposed "e" + combining acute loses the mark...
+        assert_eq!(applied(&pipeline, "cafe\u{0301}").as_deref(), Some("cafe"));
+        // ...but a precomposed "é" is left untouched at level 1.
+        assert_eq!(applied(&pipeline, "café").as_deref(), Some("café"));
+    }
+
+    #[test]
+    fn remove_diacritics_level2_strips_precomposed() {
+        let mut pipeline = FilterPipeline::new();
+        pipeline.push(Box::new(RemoveDiacritics::new(2).unwrap()));
+        assert_eq!(applied(&pipeline, "café").as_deref(), Some("cafe"));
+    }
+
+    #[test]
+    fn order_is_arg_order() {
+        // casefold then stem: "Running" -> "running" -> "run".
+        let mut pipeline = FilterPipeline::new();
+        pipeline.push(Box::new(CaseFold));
+        pipeline.push(Box::new(Stem::new("en").unwrap()));
+        assert_eq!(applied(&pipeline, "Running").as_deref(), Some("run"));
+    }
+
+    #[test]
+    fn unknown_language_is_rejected() {
+        assert!(matches!(Stem::new("xx"), Err(Error::UnknownLanguage(_))));
+    }
+}
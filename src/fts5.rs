@@ -0,0 +1,179 @@
+//! Raw FFI registration of the `icu` tokenizer against SQLite's `fts5_api`.
+//!
+//! rusqlite exposes no public hook for custom FTS5 tokenizers, so this module
+//! talks to the C `fts5_api`/`fts5_tokenizer` interface directly through
+//! `libsqlite3-sys` (re-exported as [`rusqlite::ffi`]). The `fts5_api` pointer
+//! is fetched with the documented `SELECT fts5(?1)` pointer-binding trick, then
+//! `xCreateTokenizer` installs a `fts5_tokenizer` vtable whose callbacks
+//! delegate to [`IcuTokenizer`].
+
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+use std::slice;
+
+use rusqlite::ffi;
+use rusqlite::{Connection, Error, Result};
+
+use crate::tokenizer::{IcuTokenizer, Reason};
+
+/// Install the `icu` tokenizer on `conn`.
+pub fn register(conn: &Connection) -> Result<()> {
+    unsafe {
+        let db = conn.handle();
+        let api = fts5_api_ptr(db)?;
+        let create = (*api)
+            .xCreateTokenizer
+            .ok_or_else(|| Error::ModuleError("fts5 xCreateTokenizer unavailable".to_string()))?;
+
+        let mut tokenizer = ffi::fts5_tokenizer {
+            xCreate: Some(x_create),
+            xDelete: Some(x_delete),
+            xTokenize: Some(x_tokenize),
+        };
+        // SQLite copies the vtable, so a stack value is fine.
+        let rc = create(
+            api,
+            c"icu".as_ptr(),
+            ptr::null_mut(),
+            &mut tokenizer,
+            None,
+        );
+        if rc != ffi::SQLITE_OK {
+            return Err(Error::ModuleError(format!(
+                "failed to register icu tokenizer (rc={rc})"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Fetch the `fts5_api` pointer via `SELECT fts5(?1)` with a bound pointer.
+unsafe fn fts5_api_ptr(db: *mut ffi::sqlite3) -> Result<*mut ffi::fts5_api> {
+    let mut stmt: *mut ffi::sqlite3_stmt = ptr::null_mut();
+    let rc = ffi::sqlite3_prepare_v2(
+        db,
+        c"SELECT fts5(?1)".as_ptr(),
+        -1,
+        &mut stmt,
+        ptr::null_mut(),
+    );
+    if rc != ffi::SQLITE_OK {
+        return Err(Error::ModuleError(
+            "fts5 extension is not available in this SQLite build".to_string(),
+        ));
+    }
+
+    let mut api: *mut ffi::fts5_api = ptr::null_mut();
+    ffi::sqlite3_bind_pointer(
+        stmt,
+        1,
+        &mut api as *mut *mut ffi::fts5_api as *mut c_void,
+        c"fts5_api_ptr".as_ptr(),
+        None,
+    );
+    ffi::sqlite3_step(stmt);
+    ffi::sqlite3_finalize(stmt);
+
+    if api.is_null() {
+        return Err(Error::ModuleError("could not obtain fts5_api pointer".to_string()));
+    }
+    Ok(api)
+}
+
+/// `xCreate`: parse the tokenizer arguments into a boxed [`IcuTokenizer`].
+///
+/// The body is wrapped in [`catch_unwind`](std::panic::catch_unwind) so a
+/// panic can never unwind across this C FFI boundary (which is undefined
+/// behavior); a panic is reported to SQLite as `SQLITE_ERROR`.
+unsafe extern "C" fn x_create(
+    _ctx: *mut c_void,
+    az_arg: *mut *const c_char,
+    n_arg: c_int,
+    pp_out: *mut *mut ffi::Fts5Tokenizer,
+) -> c_int {
+    let result = std::panic::catch_unwind(|| {
+        let args: Vec<String> = (0..n_arg as isize)
+            .map(|i| {
+                let ptr = *az_arg.offset(i);
+                std::ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned()
+            })
+            .collect();
+        let refs: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        IcuTokenizer::new(&refs).map(|tokenizer| {
+            *pp_out = Box::into_raw(Box::new(tokenizer)) as *mut ffi::Fts5Tokenizer;
+        })
+    });
+    match result {
+        Ok(Ok(())) => ffi::SQLITE_OK,
+        Ok(Err(_)) | Err(_) => ffi::SQLITE_ERROR,
+    }
+}
+
+/// `xDelete`: reclaim the boxed tokenizer.
+unsafe extern "C" fn x_delete(p: *mut ffi::Fts5Tokenizer) {
+    drop(Box::from_raw(p as *mut IcuTokenizer));
+}
+
+/// `xTokenize`: run the shared tokenization path and forward each token to the
+/// FTS5-supplied callback.
+unsafe extern "C" fn x_tokenize(
+    p: *mut ffi::Fts5Tokenizer,
+    p_ctx: *mut c_void,
+    flags: c_int,
+    p_text: *const c_char,
+    n_text: c_int,
+    x_token: Option<
+        unsafe extern "C" fn(*mut c_void, c_int, *const c_char, c_int, c_int, c_int) -> c_int,
+    >,
+) -> c_int {
+    let tokenizer = &*(p as *const IcuTokenizer);
+    let push = match x_token {
+        Some(cb) => cb,
+        None => return ffi::SQLITE_ERROR,
+    };
+
+    let bytes = slice::from_raw_parts(p_text as *const u8, n_text.max(0) as usize);
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(_) => return ffi::SQLITE_MISUSE,
+    };
+
+    // Guard against a panic unwinding across the C FFI boundary (UB); report it
+    // to SQLite as an error instead.
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut emit = |token: &str, start: usize, end: usize, colocated: bool| -> Result<()> {
+            let tflags = if colocated { ffi::FTS5_TOKEN_COLOCATED } else { 0 };
+            let rc = push(
+                p_ctx,
+                tflags as c_int,
+                token.as_ptr() as *const c_char,
+                token.len() as c_int,
+                start as c_int,
+                end as c_int,
+            );
+            if rc == ffi::SQLITE_OK {
+                Ok(())
+            } else {
+                Err(Error::ModuleError(format!("xToken returned {rc}")))
+            }
+        };
+        tokenizer.tokenize(reason_from_flags(flags), text, &mut emit)
+    }));
+
+    match result {
+        Ok(Ok(())) => ffi::SQLITE_OK,
+        Ok(Err(_)) | Err(_) => ffi::SQLITE_ERROR,
+    }
+}
+
+/// Map the FTS5 tokenize flags to our [`Reason`].
+fn reason_from_flags(flags: c_int) -> Reason {
+    if flags & ffi::FTS5_TOKENIZE_QUERY != 0 {
+        Reason::Query
+    } else if flags & ffi::FTS5_TOKENIZE_AUX != 0 {
+        Reason::Aux
+    } else {
+        Reason::Document
+    }
+}
@@ -0,0 +1,29 @@
+//! In-process ICU tokenizer for SQLite FTS5.
+//!
+//! This crate backs the `tokenize='icu ...'` FTS5 tokenizer. ICU supplies the
+//! Unicode word segmentation; a configurable pipeline of [`filters`] then
+//! normalizes each emitted token — NFKC, Unicode case-folding, ASCII
+//! transliteration, Snowball stemming, diacritic removal — while preserving the
+//! original source byte offsets FTS5 needs for `snippet()` and `highlight()`.
+//!
+//! The filter chain is configured through the tokenizer arguments, so
+//! `tokenize='icu nfkc casefold translit stem=en'` runs those four filters in
+//! order after ICU segments each word. See [`options::Options::parse`] for the
+//! full argument grammar.
+
+pub mod bigram;
+pub mod dict;
+pub mod error;
+pub mod filters;
+pub mod fts5;
+pub mod options;
+pub mod segmenter;
+pub mod tokenizer;
+pub mod vtab;
+
+#[cfg(feature = "loadable_extension")]
+pub mod loadable;
+
+pub use error::{Error, Result};
+pub use options::Options;
+pub use tokenizer::{register_icu_tokenizer, IcuTokenizer, Reason};
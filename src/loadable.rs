@@ -0,0 +1,36 @@
+//! Loadable-extension entry point for C and other-language consumers.
+//!
+//! This preserves the original `fts5icu` dynamic-library interface: loading the
+//! extension registers the `icu` FTS5 tokenizer on the connection. Rust
+//! applications should prefer [`crate::tokenizer::register_icu_tokenizer`],
+//! which needs no `.so`.
+//!
+//! Built only with the `loadable_extension` feature so the default build stays
+//! a plain library.
+
+use rusqlite::ffi;
+use rusqlite::{Connection, Result};
+
+use crate::tokenizer::register_icu_tokenizer;
+
+/// SQLite entry point. Named to match the historical `fts5icu` artifact so
+/// existing `load_extension('.../fts5icu')` calls keep working.
+///
+/// # Safety
+///
+/// Called by SQLite with a valid database handle while loading the extension.
+#[no_mangle]
+pub unsafe extern "C" fn sqlite3_fts5icu_init(
+    db: *mut ffi::sqlite3,
+    pz_err_msg: *mut *mut std::os::raw::c_char,
+    p_api: *const ffi::sqlite3_api_routines,
+) -> std::os::raw::c_int {
+    Connection::extension_init2(db, pz_err_msg, p_api, register_tokenizer)
+}
+
+fn register_tokenizer(conn: &Connection) -> Result<bool> {
+    register_icu_tokenizer(conn)?;
+    // `false`: plain `SQLITE_OK`, so the library is not kept loaded after the
+    // connection closes (no `SQLITE_OK_LOAD_PERMANENTLY`).
+    Ok(false)
+}
@@ -0,0 +1,271 @@
+//! Parsing of the `tokenize='icu ...'` argument list.
+//!
+//! FTS5 hands the tokenizer the arguments that follow the tokenizer name as a
+//! list of strings, e.g. `tokenize='icu nfkc casefold stem=en'` arrives as
+//! `["nfkc", "casefold", "stem=en"]`. [`Options::parse`] turns that list into a
+//! ready-to-run [`FilterPipeline`]. Sub-values may be written either attached
+//! (`stem=en`) or as a following token (`remove_diacritics 1`).
+
+use crate::bigram::BigramMode;
+use crate::dict::{StopWords, SynonymMap};
+use crate::error::{Error, Result};
+use crate::filters::{
+    CaseFold, FilterPipeline, Nfkc, RemoveDiacritics, Stem, TokenFilter, Transliterate,
+};
+use crate::segmenter::DEFAULT_LOCALE;
+
+/// Fully parsed tokenizer configuration.
+pub struct Options {
+    /// ICU locale driving word segmentation (`"und"` when none is given).
+    pub locale: String,
+    /// CJK bigram-expansion mode ([`BigramMode::Off`] by default).
+    pub bigram: BigramMode,
+    /// Post-segmentation filter chain, in argument order.
+    pub filters: FilterPipeline,
+    /// Stopword set loaded from `stopwords=<path>`, if given.
+    pub stopwords: Option<StopWords>,
+    /// Synonym map loaded from `synonyms=<path>`, if given.
+    pub synonyms: Option<SynonymMap>,
+}
+
+impl Options {
+    /// Whether `token` is a configured stopword that should be dropped.
+    pub fn is_stopword(&self, token: &str) -> bool {
+        self.stopwords.as_ref().is_some_and(|s| s.contains(token))
+    }
+
+    /// Synonyms configured for `token` (empty when none).
+    pub fn synonyms_for(&self, token: &str) -> &[String] {
+        self.synonyms.as_ref().map_or(&[], |s| s.get(token))
+    }
+}
+
+impl Options {
+    /// Parse the argument list that follows `icu` in a `tokenize=` clause.
+    ///
+    /// A leading token that is not a recognized option keyword is taken as the
+    /// ICU locale, so `tokenize='icu ja_JP nfkc'` selects the `ja_JP` locale and
+    /// then enables NFKC normalization.
+    pub fn parse(args: &[&str]) -> Result<Self> {
+        let mut locale = DEFAULT_LOCALE.to_string();
+        let mut rest = args;
+        if let Some(first) = args.first() {
+            if !is_option_keyword(first) {
+                locale = (*first).to_string();
+                rest = &args[1..];
+            }
+        }
+
+        let mut filters = FilterPipeline::new();
+        let mut bigram = BigramMode::Off;
+        let mut stopwords = None;
+        let mut synonyms = None;
+        let args = rest;
+        let mut iter = args.iter().enumerate();
+
+        while let Some((idx, arg)) = iter.next() {
+            let (key, attached) = split_option(arg);
+            // Segmentation modes and dictionaries are not token filters.
+            match key {
+                "bigram" => {
+                    bigram = BigramMode::Standalone;
+                    continue;
+                }
+                "bigram_colocated" => {
+                    bigram = BigramMode::Colocated;
+                    continue;
+                }
+                "stopwords" => {
+                    let path = value_for("stopwords", attached, &mut iter, args, idx)?;
+                    stopwords = Some(StopWords::load(&path)?);
+                    continue;
+                }
+                "synonyms" => {
+                    let path = value_for("synonyms", attached, &mut iter, args, idx)?;
+                    synonyms = Some(SynonymMap::load(&path)?);
+                    continue;
+                }
+                _ => {}
+            }
+            let filter: Box<dyn TokenFilter> = match key {
+                "nfkc" => Box::new(Nfkc),
+                "casefold" => Box::new(CaseFold),
+                "translit" => Box::new(Transliterate),
+                "stem" => {
+                    let lang = value_for("stem", attached, &mut iter, args, idx)?;
+                    Box::new(Stem::new(&lang)?)
+                }
+                "remove_diacritics" => {
+                    let raw = value_for("remove_diacritics", attached, &mut iter, args, idx)?;
+                    let level = raw.parse::<u8>().map_err(|_| Error::InvalidValue {
+                        option: "remove_diacritics",
+                        value: raw.clone(),
+                    })?;
+                    Box::new(RemoveDiacritics::new(level)?)
+                }
+                other => return Err(Error::UnknownOption(other.to_string())),
+            };
+            filters.push(filter);
+        }
+
+        // Dictionary entries are written in surface form; rewrite their keys
+        // through the now-complete filter chain so lookups during tokenization
+        // (which use the filtered token) match.
+        if let Some(stopwords) = stopwords.as_mut() {
+            stopwords.normalize_keys(&filters);
+        }
+        if let Some(synonyms) = synonyms.as_mut() {
+            synonyms.normalize_keys(&filters);
+        }
+
+        Ok(Options { locale, bigram, filters, stopwords, synonyms })
+    }
+}
+
+/// Whether `arg` names a known option (rather than a leading locale).
+fn is_option_keyword(arg: &str) -> bool {
+    let key = arg.split_once('=').map_or(arg, |(key, _)| key);
+    matches!(
+        key,
+        "nfkc"
+            | "casefold"
+            | "translit"
+            | "stem"
+            | "remove_diacritics"
+            | "bigram"
+            | "bigram_colocated"
+            | "stopwords"
+            | "synonyms"
+    )
+}
+
+/// Split `key=value` into its parts; returns `(key, Some(value))` or `(arg, None)`.
+fn split_option(arg: &str) -> (&str, Option<&str>) {
+    match arg.split_once('=') {
+        Some((key, value)) => (key, Some(value)),
+        None => (arg, None),
+    }
+}
+
+/// Resolve an option value from either the attached `key=value` form or the
+/// following token, advancing the iterator in the latter case.
+fn value_for<'a>(
+    option: &'static str,
+    attached: Option<&str>,
+    iter: &mut std::iter::Enumerate<std::slice::Iter<'a, &'a str>>,
+    args: &[&str],
+    idx: usize,
+) -> Result<String> {
+    if let Some(value) = attached {
+        return Ok(value.to_string());
+    }
+    // No attached value: consume the next token only when it is not itself an
+    // option — neither a `key=value` form nor a bare option keyword — so a
+    // forgotten value (`stem casefold`) fails as a missing value rather than
+    // silently swallowing the following option.
+    match args.get(idx + 1) {
+        Some(next) if !next.contains('=') && !is_option_keyword(next) => {
+            iter.next();
+            Ok((*next).to_string())
+        }
+        _ => Err(Error::MissingValue(option)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_args_yield_default_locale_and_empty_pipeline() {
+        let options = Options::parse(&[]).unwrap();
+        assert_eq!(options.locale, "und");
+        assert!(options.filters.is_empty());
+    }
+
+    #[test]
+    fn leading_locale_is_captured() {
+        let options = Options::parse(&["ja_JP", "nfkc"]).unwrap();
+        assert_eq!(options.locale, "ja_JP");
+        assert_eq!(options.filters.apply("ＡＢＣ").as_deref(), Some("ABC"));
+    }
+
+    #[test]
+    fn no_locale_when_first_arg_is_an_option() {
+        let options = Options::parse(&["nfkc"]).unwrap();
+        assert_eq!(options.locale, "und");
+    }
+
+    #[test]
+    fn attached_and_detached_values_agree() {
+        let attached = Options::parse(&["stem=en", "remove_diacritics=2"]).unwrap();
+        let detached = Options::parse(&["stem", "en", "remove_diacritics", "2"]).unwrap();
+        assert_eq!(
+            attached.filters.apply("running").as_deref(),
+            detached.filters.apply("running").as_deref(),
+        );
+    }
+
+    #[test]
+    fn full_chain_normalizes() {
+        let options = Options::parse(&["nfkc", "casefold", "translit", "stem=en"]).unwrap();
+        assert_eq!(options.filters.apply("Cafés").as_deref(), Some("cafe"));
+    }
+
+    #[test]
+    fn bigram_modes_are_parsed() {
+        use crate::bigram::BigramMode;
+        assert_eq!(Options::parse(&["bigram"]).unwrap().bigram, BigramMode::Standalone);
+        assert_eq!(
+            Options::parse(&["bigram_colocated"]).unwrap().bigram,
+            BigramMode::Colocated,
+        );
+        assert_eq!(Options::parse(&["nfkc"]).unwrap().bigram, BigramMode::Off);
+    }
+
+    #[test]
+    fn locale_and_bigram_compose() {
+        let options = Options::parse(&["ja_JP", "bigram", "casefold"]).unwrap();
+        assert_eq!(options.locale, "ja_JP");
+        assert_eq!(options.bigram, crate::bigram::BigramMode::Standalone);
+        assert!(!options.filters.is_empty());
+    }
+
+    #[test]
+    fn dictionaries_are_loaded_at_parse_time() {
+        use std::io::Write;
+        let mut path = std::env::temp_dir();
+        path.push("icu_opt_stop.txt");
+        writeln!(std::fs::File::create(&path).unwrap(), "the").unwrap();
+        let path = path.to_str().unwrap();
+
+        let options = Options::parse(&["en", &format!("stopwords={}", path)]).unwrap();
+        assert_eq!(options.locale, "en");
+        assert!(options.is_stopword("the"));
+        assert!(!options.is_stopword("cat"));
+    }
+
+    #[test]
+    fn unknown_option_is_rejected() {
+        // A lone leading token is read as a locale; an unknown token after one
+        // recognized option is a genuine unknown-option error.
+        assert!(matches!(
+            Options::parse(&["nfkc", "bogus"]),
+            Err(Error::UnknownOption(_))
+        ));
+    }
+
+    #[test]
+    fn missing_value_is_rejected() {
+        assert!(matches!(Options::parse(&["stem"]), Err(Error::MissingValue("stem"))));
+    }
+
+    #[test]
+    fn option_value_does_not_swallow_following_keyword() {
+        // `stem` with a forgotten value must not consume the `casefold` option.
+        assert!(matches!(
+            Options::parse(&["stem", "casefold"]),
+            Err(Error::MissingValue("stem"))
+        ));
+    }
+}
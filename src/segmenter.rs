@@ -0,0 +1,121 @@
+//! Locale-aware word segmentation backed by ICU's `UBreakIterator`.
+//!
+//! ICU's dictionary-based segmentation is locale sensitive: Thai and Lao have
+//! no inter-word spaces, and Japanese/Chinese compound splits differ by locale.
+//! A [`Segmenter`] is constructed with the locale taken from the tokenizer
+//! arguments (`tokenize='icu ja_JP'`) and defaults to the ICU root/`und` locale
+//! so databases that pass no locale keep their previous behavior.
+//!
+//! ICU operates on UTF-16, but FTS5 addresses the source document in UTF-8
+//! bytes. [`Segmenter::segment`] therefore maps every UTF-16 boundary ICU
+//! reports back to a UTF-8 byte offset before yielding it, so emitted ranges
+//! line up with the bytes `snippet()`/`highlight()` expect.
+
+use rust_icu_sys::{UBreakIteratorType, UWordBreak};
+use rust_icu_ubrk::UBreakIterator;
+
+use crate::error::{Error, Result};
+
+/// The ICU root locale, used when the tokenizer arguments name none.
+pub const DEFAULT_LOCALE: &str = "und";
+
+/// A segmented span of the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Word {
+    /// Start offset in the source string, in UTF-8 bytes.
+    pub start: usize,
+    /// End offset in the source string, in UTF-8 bytes (exclusive).
+    pub end: usize,
+}
+
+/// A reusable, locale-configured word segmenter.
+pub struct Segmenter {
+    locale: String,
+}
+
+impl Segmenter {
+    /// Build a segmenter for `locale` (e.g. `"ja_JP"`, `"th"`, or `"und"`).
+    ///
+    /// The locale is validated here by constructing a break iterator once, so a
+    /// bad `tokenize='icu <locale>'` fails cleanly at `CREATE VIRTUAL TABLE`
+    /// rather than panicking across the C boundary on the first row.
+    pub fn new(locale: &str) -> Result<Self> {
+        UBreakIterator::try_new(UBreakIteratorType::UBRK_WORD, locale, "")
+            .map_err(|_| Error::InvalidLocale(locale.to_string()))?;
+        Ok(Segmenter { locale: locale.to_string() })
+    }
+
+    /// The locale this segmenter was built with.
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Segment `text` into words, returning their UTF-8 byte ranges in order.
+    ///
+    /// Non-word spans (whitespace, punctuation) are skipped using ICU's word
+    /// rule status, matching how a word-boundary break iterator distinguishes
+    /// dictionary words from separators.
+    pub fn segment(&self, text: &str) -> Vec<Word> {
+        let mut words = Vec::new();
+        if text.is_empty() {
+            return words;
+        }
+
+        let byte_for = utf16_to_byte_map(text);
+        let mut iter = UBreakIterator::try_new(UBreakIteratorType::UBRK_WORD, &self.locale, text)
+            .expect("ICU word break iterator construction failed");
+
+        let mut prev = iter.first();
+        while let Some(boundary) = iter.next() {
+            // `get_rule_status` describes the span ending at the current
+            // boundary; statuses at or above `UBRK_WORD_NONE_LIMIT` are words
+            // (numbers, letters, kana, ideographs), while 0 marks separators.
+            if iter.get_rule_status() >= UWordBreak::UBRK_WORD_NONE_LIMIT as i32 {
+                words.push(Word {
+                    start: byte_for[prev as usize],
+                    end: byte_for[boundary as usize],
+                });
+            }
+            prev = boundary;
+        }
+        words
+    }
+}
+
+/// Build a lookup from UTF-16 code-unit index to UTF-8 byte offset.
+///
+/// The returned vector has `utf16_len + 1` entries so that the final boundary
+/// ICU reports (the end of the string) maps to `text.len()`.
+fn utf16_to_byte_map(text: &str) -> Vec<usize> {
+    let mut map = Vec::with_capacity(text.len() + 1);
+    for (byte_idx, ch) in text.char_indices() {
+        for _ in 0..ch.len_utf16() {
+            map.push(byte_idx);
+        }
+    }
+    map.push(text.len());
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_map_handles_astral_and_ascii() {
+        // "a𐐷b": 'a' (1 byte, 1 u16), '𐐷' (4 bytes, 2 u16), 'b' (1 byte, 1 u16).
+        let map = utf16_to_byte_map("a𐐷b");
+        assert_eq!(map, vec![0, 1, 1, 5, 6]);
+    }
+
+    #[test]
+    fn empty_text_segments_to_nothing() {
+        let segmenter = Segmenter::new(DEFAULT_LOCALE).unwrap();
+        assert!(segmenter.segment("").is_empty());
+    }
+
+    #[test]
+    fn named_locale_constructs() {
+        assert!(Segmenter::new("ja_JP").is_ok());
+    }
+}
@@ -0,0 +1,267 @@
+//! The FTS5 tokenizer itself: segmentation + filters + bigram expansion wired
+//! into SQLite's FTS5 tokenizer API (registered through [`crate::fts5`]).
+//!
+//! [`register_icu_tokenizer`] installs the `icu` tokenizer directly on a
+//! [`rusqlite::Connection`], so applications that statically link SQLite
+//! (`bundled`) get `tokenize='icu ...'` with no prebuilt `.so` to ship or
+//! download. The loadable-extension entry point (see [`crate::loadable`]) is
+//! kept for C and other-language consumers.
+//!
+//! [`tokenize_into`] is the single authoritative tokenization path. Both this
+//! FTS5 hook and the `icu_tokenize()` table-valued function call it, so their
+//! output can never drift apart.
+
+use rusqlite::{Connection, Result};
+
+use crate::bigram::{bigrams, BigramMode};
+use crate::options::Options;
+use crate::segmenter::Segmenter;
+
+/// Why FTS5 is tokenizing. The bigram expansion runs identically for documents
+/// and queries so phrase matches line up; the reason is threaded through for
+/// parity with the C tokenizer API and possible future specialization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// Indexing a document (`FTS5_TOKENIZE_DOCUMENT`).
+    Document,
+    /// Parsing a `MATCH` query (`FTS5_TOKENIZE_QUERY`).
+    Query,
+    /// Tokenizing for an auxiliary function (`FTS5_TOKENIZE_AUX`).
+    Aux,
+}
+
+/// A configured ICU tokenizer instance, created once per FTS5 table.
+pub struct IcuTokenizer {
+    options: Options,
+    segmenter: Segmenter,
+}
+
+impl IcuTokenizer {
+    /// Build a tokenizer from the argument list following `icu` in `tokenize=`.
+    pub fn new(args: &[&str]) -> Result<Self> {
+        let options = Options::parse(args).map_err(to_sqlite_err)?;
+        let segmenter = Segmenter::new(&options.locale).map_err(to_sqlite_err)?;
+        Ok(IcuTokenizer { options, segmenter })
+    }
+
+    /// Tokenize `text`, invoking `push_token` for each emitted token.
+    ///
+    /// `push_token` receives `(text, start, end, colocated)` where `start`/`end`
+    /// are UTF-8 byte offsets into the original `text` and `colocated` marks a
+    /// synonym sharing the previous token's position.
+    pub fn tokenize(
+        &self,
+        reason: Reason,
+        text: &str,
+        push_token: &mut dyn FnMut(&str, usize, usize, bool) -> Result<()>,
+    ) -> Result<()> {
+        tokenize_into(&self.options, &self.segmenter, reason, text, push_token)
+    }
+}
+
+/// Run the full segmentation + filter + bigram pipeline over `text`.
+///
+/// Dictionary words are emitted in document order. Depending on the configured
+/// [`BigramMode`], CJK bigrams are either merged with them in byte order as
+/// standalone tokens over the CJK runs of the whole text, or emitted right
+/// after each dictionary word as colocated synonyms anchored to the word that
+/// contains them.
+pub fn tokenize_into(
+    options: &Options,
+    segmenter: &Segmenter,
+    _reason: Reason,
+    text: &str,
+    push_token: &mut dyn FnMut(&str, usize, usize, bool) -> Result<()>,
+) -> Result<()> {
+    let words = segmenter.segment(text);
+
+    match options.bigram {
+        BigramMode::Off => {
+            for word in &words {
+                emit_token(options, &text[word.start..word.end], word.start, word.end, false, true, push_token)?;
+            }
+        }
+        BigramMode::Standalone => {
+            // Standalone bigrams run over the maximal CJK spans of the *source*,
+            // not within individual ICU words, so compounds ICU over-splits
+            // (e.g. `全文検索` → `全文|検索`) still yield the crossing bigram
+            // `文検`. Dictionary words and run bigrams are both in byte order, so
+            // merging them keeps emitted start offsets nondecreasing — which FTS5
+            // requires for snippet()/highlight() and detail=full. Bigrams are
+            // plain fragments, not surface words, so they are not synonym-expanded.
+            let grams = bigrams(text);
+            let mut wi = 0;
+            let mut gi = 0;
+            while wi < words.len() || gi < grams.len() {
+                let take_word = match (words.get(wi), grams.get(gi)) {
+                    (Some(w), Some(g)) => w.start <= g.start,
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                };
+                let (span, expand_synonyms) = if take_word {
+                    wi += 1;
+                    (words[wi - 1], true)
+                } else {
+                    gi += 1;
+                    (grams[gi - 1], false)
+                };
+                emit_token(options, &text[span.start..span.end], span.start, span.end, false, expand_synonyms, push_token)?;
+            }
+        }
+        BigramMode::Colocated => {
+            for word in &words {
+                let emitted = emit_token(options, &text[word.start..word.end], word.start, word.end, false, true, push_token)?;
+                // Colocated bigrams attach to the dictionary word just emitted, so
+                // skip them when the word was dropped (stopword or filtered away):
+                // a leading colocated token has nothing to colocate onto.
+                if !emitted {
+                    continue;
+                }
+                // Colocated bigrams share the dictionary word's offsets so
+                // snippet()/highlight() still cover the whole word.
+                for gram in bigrams(&text[word.start..word.end]) {
+                    let src = &text[word.start + gram.start..word.start + gram.end];
+                    emit_token(options, src, word.start, word.end, true, false, push_token)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Filter `src` and push the result (if any) with the given source range,
+/// returning whether a token was emitted.
+///
+/// `colocated` flags the pushed token as sharing the previous token's position.
+/// `expand_synonyms` additionally emits each configured synonym as a colocated
+/// token; it is set only for surface dictionary words, never for bigram
+/// fragments, which have no synonyms of their own.
+fn emit_token(
+    options: &Options,
+    src: &str,
+    range_start: usize,
+    range_end: usize,
+    colocated: bool,
+    expand_synonyms: bool,
+    push_token: &mut dyn FnMut(&str, usize, usize, bool) -> Result<()>,
+) -> Result<bool> {
+    let Some(token) = options.filters.apply(src) else {
+        return Ok(false);
+    };
+    if token.is_empty() || options.is_stopword(&token) {
+        return Ok(false);
+    }
+    push_token(&token, range_start, range_end, colocated)?;
+    if expand_synonyms {
+        // Each synonym runs through the same filter chain as ordinary text so it
+        // is indexed in the normalized form a query will produce (e.g.
+        // casefold/stem), colocated with the surface token.
+        for synonym in options.synonyms_for(&token) {
+            if let Some(normalized) = options.filters.apply(synonym) {
+                if !normalized.is_empty() {
+                    push_token(&normalized, range_start, range_end, true)?;
+                }
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Register the `icu` FTS5 tokenizer and the `icu_tokenize()` table-valued
+/// function on `conn`, so `tokenize='icu ...'` works without loading a dynamic
+/// library.
+pub fn register_icu_tokenizer(conn: &Connection) -> Result<()> {
+    crate::fts5::register(conn)?;
+    crate::vtab::register_icu_tokenize_function(conn)
+}
+
+/// Convert a configuration error into a SQLite error for `xCreate`.
+fn to_sqlite_err(err: crate::error::Error) -> rusqlite::Error {
+    rusqlite::Error::ModuleError(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(args: &[&str], text: &str) -> Vec<(String, usize, usize, bool)> {
+        let tok = IcuTokenizer::new(args).unwrap();
+        let mut out = Vec::new();
+        tok.tokenize(Reason::Document, text, &mut |t, s, e, c| {
+            out.push((t.to_string(), s, e, c));
+            Ok(())
+        })
+        .unwrap();
+        out
+    }
+
+    #[test]
+    fn offsets_survive_rewrites() {
+        // casefold + translit rewrite "Café" to "cafe", but the token keeps the
+        // original 5-byte range.
+        let tokens = collect(&["casefold", "translit"], "Café");
+        assert_eq!(tokens, vec![("cafe".to_string(), 0, "Café".len(), false)]);
+    }
+
+    #[test]
+    fn colocated_bigrams_share_word_range() {
+        let tokens = collect(&["bigram_colocated"], "全文検索");
+        // The dictionary word (whatever ICU returns) is non-colocated; the
+        // bigrams that follow are colocated synonyms sharing its range.
+        assert!(tokens.iter().any(|(_, _, _, colocated)| *colocated));
+        let mut word_range = None;
+        for (_, start, end, colocated) in &tokens {
+            if *colocated {
+                assert_eq!(Some((*start, *end)), word_range, "bigram range differs: {tokens:?}");
+            } else {
+                word_range = Some((*start, *end));
+            }
+        }
+    }
+
+    #[test]
+    fn standalone_bigrams_span_whole_cjk_run() {
+        // `全文検索` is one CJK run, so the crossing bigram `文検` is emitted even
+        // if ICU segments it into separate dictionary words — that recall is the
+        // whole point of the mode. Offsets must also stay nondecreasing.
+        let tokens = collect(&["bigram"], "全文検索");
+        let texts: Vec<&str> = tokens.iter().map(|(t, ..)| t.as_str()).collect();
+        assert!(texts.contains(&"文検"), "crossing bigram missing: {texts:?}");
+
+        let mut last = 0;
+        for (_, start, ..) in &tokens {
+            assert!(*start >= last, "offsets not nondecreasing: {tokens:?}");
+            last = *start;
+        }
+    }
+
+    #[test]
+    fn standalone_bigram_fragments_are_not_synonym_expanded() {
+        use std::io::Write;
+        let mut path = std::env::temp_dir();
+        path.push("icu_tok_syn.tsv");
+        writeln!(std::fs::File::create(&path).unwrap(), "文検\tCROSS").unwrap();
+        let path = path.to_str().unwrap();
+
+        let tokens = collect(&["bigram", &format!("synonyms={path}")], "全文検索");
+        let texts: Vec<&str> = tokens.iter().map(|(t, ..)| t.as_str()).collect();
+        // The bigram fragment `文検` is emitted, but synonym expansion applies to
+        // surface words only, so its synonym `CROSS` must not appear.
+        assert!(texts.contains(&"文検"));
+        assert!(!texts.contains(&"CROSS"), "bigram fragment was synonym-expanded: {texts:?}");
+    }
+
+    #[test]
+    fn colocated_bigram_skipped_when_word_is_stopword() {
+        use std::io::Write;
+        let mut path = std::env::temp_dir();
+        path.push("icu_tok_stop_cat.txt");
+        writeln!(std::fs::File::create(&path).unwrap(), "猫").unwrap();
+        let path = path.to_str().unwrap();
+
+        // `猫` is one ICU word and a stopword, so it is dropped; its colocated
+        // bigram must not be emitted with nothing to colocate onto.
+        let tokens = collect(&["bigram_colocated", &format!("stopwords={path}")], "猫");
+        assert!(tokens.is_empty(), "dropped word still emitted a colocated bigram: {tokens:?}");
+    }
+}
@@ -0,0 +1,202 @@
+//! The `icu_tokenize(text, locale)` table-valued function.
+//!
+//! Implemented as an eponymous-only virtual table in the style of rusqlite's
+//! `series`/`carray` modules. It returns one row per emitted token:
+//!
+//! ```sql
+//! SELECT token, start, end, colocated
+//!   FROM icu_tokenize('全文検索', 'icu ja_JP bigram_colocated');
+//! ```
+//!
+//! The `locale`/options argument accepts the exact same string as the FTS5
+//! tokenizer (`'icu ja_JP nfkc stem=ja'`), and the rows are produced by
+//! [`crate::tokenizer::tokenize_into`] — the same code path the tokenizer uses —
+//! so the output is authoritative for debugging and custom indexing.
+
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::os::raw::c_int;
+
+use rusqlite::vtab::{Context, Filters, IndexInfo, Module, VTab, VTabConnection, VTabCursor};
+use rusqlite::{ffi, Connection, Result};
+
+use crate::options::Options;
+use crate::segmenter::Segmenter;
+use crate::tokenizer::{tokenize_into, Reason};
+
+/// Input-argument column indices (hidden) and output columns.
+const COL_TOKEN: c_int = 0;
+const COL_START: c_int = 1;
+const COL_END: c_int = 2;
+const COL_COLOCATED: c_int = 3;
+const COL_TEXT: c_int = 4;
+const COL_OPTIONS: c_int = 5;
+
+/// A single tokenized row materialized by the cursor.
+struct Row {
+    token: String,
+    start: i64,
+    end: i64,
+    colocated: bool,
+}
+
+/// The eponymous virtual table. It holds no per-table state; all work happens
+/// in the cursor once the `text` argument is bound.
+#[repr(C)]
+struct IcuTokenizeTab {
+    base: ffi::sqlite3_vtab,
+}
+
+unsafe impl<'vtab> VTab<'vtab> for IcuTokenizeTab {
+    type Aux = ();
+    type Cursor = IcuTokenizeCursor;
+
+    fn connect(
+        _db: &mut VTabConnection,
+        _aux: Option<&()>,
+        _module_name: &[u8],
+        _database_name: &[u8],
+        _table_name: &[u8],
+        _args: &[&[u8]],
+    ) -> Result<(Cow<'static, CStr>, Self)> {
+        let schema = c"CREATE TABLE x(\
+             token TEXT, \
+             start INTEGER, \
+             end INTEGER, \
+             colocated INTEGER, \
+             text HIDDEN, \
+             options HIDDEN)";
+        Ok((Cow::Borrowed(schema), IcuTokenizeTab { base: ffi::sqlite3_vtab::default() }))
+    }
+
+    fn best_index(&self, info: &mut IndexInfo) -> Result<bool> {
+        // Require the `text` argument; `options` is optional. Encode which
+        // hidden columns are constrained in `idx_num` so `filter` knows which
+        // bound values to read. Collect the constrained columns first so the
+        // immutable `constraints()` borrow is released before we mutate usages.
+        let mut idx_num = 0;
+        let mut bound: Vec<(usize, c_int)> = Vec::new();
+        for (i, constraint) in info.constraints().enumerate() {
+            if !constraint.is_usable() {
+                continue;
+            }
+            match constraint.column() {
+                COL_TEXT => {
+                    idx_num |= 1;
+                    bound.push((i, COL_TEXT));
+                }
+                COL_OPTIONS => {
+                    idx_num |= 2;
+                    bound.push((i, COL_OPTIONS));
+                }
+                _ => {}
+            }
+        }
+        if idx_num & 1 == 0 {
+            return Ok(false);
+        }
+        // Bind `text` first so `filter` reads argv 0 == text, argv 1 == options.
+        bound.sort_by_key(|(_, col)| *col);
+        for (argv, (i, _)) in bound.into_iter().enumerate() {
+            let mut usage = info.constraint_usage(i);
+            usage.set_argv_index(argv as c_int + 1);
+            usage.set_omit(true);
+        }
+        info.set_idx_num(idx_num);
+        info.set_estimated_cost(1_000.0);
+        Ok(true)
+    }
+
+    fn open(&'vtab mut self) -> Result<IcuTokenizeCursor> {
+        Ok(IcuTokenizeCursor::default())
+    }
+}
+
+/// Cursor that materializes every token up front in [`VTabCursor::filter`].
+#[derive(Default)]
+#[repr(C)]
+struct IcuTokenizeCursor {
+    base: ffi::sqlite3_vtab_cursor,
+    rows: Vec<Row>,
+    pos: usize,
+}
+
+unsafe impl VTabCursor for IcuTokenizeCursor {
+    fn filter(&mut self, idx_num: c_int, _idx_str: Option<&str>, args: &Filters<'_>) -> Result<()> {
+        let mut argv = 0;
+        let text: String = {
+            let v = args.get::<String>(argv)?;
+            argv += 1;
+            v
+        };
+        // The options string defaults to bare `icu` (root locale, no filters).
+        let options_arg: String = if idx_num & 2 != 0 {
+            args.get::<String>(argv)?
+        } else {
+            "icu".to_string()
+        };
+
+        let parsed = parse_options(&options_arg)?;
+        let options = Options::parse(&parsed.iter().map(String::as_str).collect::<Vec<_>>())
+            .map_err(|e| rusqlite::Error::ModuleError(e.to_string()))?;
+        let segmenter = Segmenter::new(&options.locale)
+            .map_err(|e| rusqlite::Error::ModuleError(e.to_string()))?;
+
+        let mut rows = Vec::new();
+        tokenize_into(&options, &segmenter, Reason::Aux, &text, &mut |tok, start, end, colocated| {
+            rows.push(Row {
+                token: tok.to_string(),
+                start: start as i64,
+                end: end as i64,
+                colocated,
+            });
+            Ok(())
+        })?;
+
+        self.rows = rows;
+        self.pos = 0;
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<()> {
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn eof(&self) -> bool {
+        self.pos >= self.rows.len()
+    }
+
+    fn column(&self, ctx: &mut Context, col: c_int) -> Result<()> {
+        let row = &self.rows[self.pos];
+        match col {
+            COL_TOKEN => ctx.set_result(&row.token),
+            COL_START => ctx.set_result(&row.start),
+            COL_END => ctx.set_result(&row.end),
+            COL_COLOCATED => ctx.set_result(&(row.colocated as i64)),
+            // Hidden input columns read back as NULL.
+            _ => ctx.set_result(&rusqlite::types::Null),
+        }
+    }
+
+    fn rowid(&self) -> Result<i64> {
+        Ok(self.pos as i64)
+    }
+}
+
+/// Split the options argument, tolerating a leading `icu` tokenizer name so the
+/// function accepts the identical string used in `tokenize='icu ...'`.
+fn parse_options(arg: &str) -> Result<Vec<String>> {
+    let mut parts = arg.split_whitespace().map(str::to_string).collect::<Vec<_>>();
+    if parts.first().map(String::as_str) == Some("icu") {
+        parts.remove(0);
+    }
+    Ok(parts)
+}
+
+/// Register the `icu_tokenize` table-valued function on `conn`.
+pub fn register_icu_tokenize_function(conn: &Connection) -> Result<()> {
+    const MODULE: Module<IcuTokenizeTab> = Module::eponymous_only_module();
+    let aux: Option<()> = None;
+    conn.create_module("icu_tokenize", &MODULE, aux)
+}